@@ -1,12 +1,13 @@
 use proc_macro2::{Span, TokenStream};
 use quote::{format_ident, quote};
 use syn::{
-    parse_macro_input, Data, DeriveInput, Fields, FieldsNamed, GenericArgument, Ident, Lit, Meta,
-    MetaNameValue, NestedMeta, PathArguments, PathSegment, Type, Visibility,
+    parse_macro_input, Data, DeriveInput, Field, Fields, FieldsNamed, GenericArgument, Ident,
+    ImplGenerics, Lit, Meta, MetaNameValue, NestedMeta, PathArguments, PathSegment, Type,
+    TypeGenerics, Visibility, WhereClause,
 };
 
 enum LitOrError {
-    Lit(String),
+    Lit(syn::LitStr),
     Error(syn::Error),
 }
 
@@ -17,6 +18,7 @@ pub fn derive(input: proc_macro::TokenStream) -> proc_macro::TokenStream {
     let ident = input.ident;
     let vis = input.vis;
     let builder_name = format_ident!("{}Builder", ident);
+    let struct_setter_into = has_setter_into(&input.attrs);
 
     let fields = match input.data {
         Data::Struct(data) => match data.fields {
@@ -34,12 +36,43 @@ pub fn derive(input: proc_macro::TokenStream) -> proc_macro::TokenStream {
         }
     };
 
-    let builder_struct = build_builder_struct(&fields, &builder_name, &vis);
-    let builder_impl = build_builder_impl(&fields, &builder_name, &ident);
-    let struct_impl = build_struct_impl(&fields, &builder_name, &ident);
+    let generics = input.generics;
+    let (impl_generics, ty_generics, where_clause) = generics.split_for_impl();
+
+    let mut errors: Vec<syn::Error> = Vec::new();
+
+    let builder_struct = build_builder_struct(&fields, &builder_name, &vis, &ty_generics);
+    let builder_error = build_builder_error(&fields, &builder_name, &vis);
+    let builder_impl = build_builder_impl(
+        &fields,
+        &builder_name,
+        &ident,
+        &impl_generics,
+        &ty_generics,
+        where_clause,
+        struct_setter_into,
+        &mut errors,
+    );
+    let struct_impl = build_struct_impl(
+        &fields,
+        &builder_name,
+        &ident,
+        &impl_generics,
+        &ty_generics,
+        where_clause,
+    );
+
+    let mut errors = errors.into_iter();
+    if let Some(mut combined) = errors.next() {
+        for err in errors {
+            combined.combine(err);
+        }
+        return combined.to_compile_error().into();
+    }
 
     let expand = quote! {
         #builder_struct
+        #builder_error
         #builder_impl
         #struct_impl
     };
@@ -50,6 +83,7 @@ fn build_builder_struct(
     fields: &FieldsNamed,
     builder_name: &Ident,
     visibility: &Visibility,
+    ty_generics: &TypeGenerics,
 ) -> TokenStream {
     let struct_fields = fields
         .named
@@ -60,7 +94,7 @@ fn build_builder_struct(
             (ident.unwrap(), ty)
         })
         .map(|(ident, ty)| {
-            if is_vector(&ty) {
+            if is_vector(ty) || is_map(ty) {
                 quote! {
                     #ident: #ty
                 }
@@ -71,96 +105,231 @@ fn build_builder_struct(
             }
         });
     quote! {
-        #visibility struct #builder_name {
+        #visibility struct #builder_name #ty_generics {
             #(#struct_fields),*
         }
     }
-    .into()
 }
 
-fn build_builder_impl(
-    fields: &FieldsNamed,
-    builder_name: &Ident,
-    struct_name: &Ident,
-) -> TokenStream {
-    let checks = fields
+fn required_fields(fields: &FieldsNamed) -> Vec<&Field> {
+    fields
         .named
         .iter()
         .filter(|field| !is_option(&field.ty))
         .filter(|field| !is_vector(&field.ty))
-        .map(|field| {
-            let ident = field.ident.as_ref();
-            let err = format!("Required field '{}' is missing", ident.unwrap().to_string());
-            quote! {
-                if self.#ident.is_none() {
-                    return Err(#err.into());
+        .filter(|field| !is_map(&field.ty))
+        .filter(|field| field_default_source(field).is_none())
+        .collect()
+}
+
+fn missing_variant_ident(field_ident: &Ident) -> Ident {
+    format_ident!("Missing{}", to_pascal_case(&field_ident.to_string()))
+}
+
+fn to_pascal_case(name: &str) -> String {
+    name.split('_')
+        .map(|part| {
+            let mut chars = part.chars();
+            match chars.next() {
+                Some(first) => first.to_uppercase().collect::<String>() + chars.as_str(),
+                None => String::new(),
+            }
+        })
+        .collect()
+}
+
+fn build_builder_error(
+    fields: &FieldsNamed,
+    builder_name: &Ident,
+    visibility: &Visibility,
+) -> TokenStream {
+    let error_name = format_ident!("{}Error", builder_name);
+    let required = required_fields(fields);
+
+    let variants = required.iter().map(|field| {
+        let variant = missing_variant_ident(field.ident.as_ref().unwrap());
+        quote! { #variant }
+    });
+
+    let display_arms = required.iter().map(|field| {
+        let variant = missing_variant_ident(field.ident.as_ref().unwrap());
+        let msg = format!(
+            "Required field '{}' is missing",
+            field.ident.as_ref().unwrap()
+        );
+        quote! {
+            #error_name::#variant => write!(f, #msg),
+        }
+    });
+
+    quote! {
+        #[derive(Debug)]
+        #visibility enum #error_name {
+            #(#variants),*
+        }
+
+        impl std::fmt::Display for #error_name {
+            fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+                match *self {
+                    #(#display_arms)*
                 }
             }
-        });
+        }
 
-    let setters = fields.named.iter().map(|field| {
-        let ident_each_name = field
-            .attrs
-            .first()
-            .map(|attr| match attr.parse_meta() {
-                Ok(Meta::List(list)) => match list.nested.first() {
-                    Some(NestedMeta::Meta(Meta::NameValue(MetaNameValue {
-                        ref path,
-                        eq_token: _,
-                        lit: Lit::Str(ref str),
-                    }))) => {
-                        if let Some(name) = path.segments.first() {
-                            if name.ident.to_string() != "each" {
-                                return Some(LitOrError::Error(syn::Error::new_spanned(
-                                    list,
-                                    "expected `builder(each = \"...\")`",
-                                )));
-                            }
-                        }
+        impl std::error::Error for #error_name {}
+    }
+}
 
-                        Some(LitOrError::Lit(str.value()))
+#[allow(clippy::too_many_arguments)]
+fn build_builder_impl(
+    fields: &FieldsNamed,
+    builder_name: &Ident,
+    struct_name: &Ident,
+    impl_generics: &ImplGenerics,
+    ty_generics: &TypeGenerics,
+    where_clause: Option<&WhereClause>,
+    struct_setter_into: bool,
+    errors: &mut Vec<syn::Error>,
+) -> TokenStream {
+    let error_name = format_ident!("{}Error", builder_name);
+    let checks = required_fields(fields).into_iter().map(|field| {
+        let ident = field.ident.as_ref();
+        let variant = missing_variant_ident(ident.unwrap());
+        quote! {
+            if self.#ident.is_none() {
+                return Err(#error_name::#variant);
+            }
+        }
+    });
+
+    let setters = fields.named.iter().map(|field| {
+        let ident_each_name =
+            builder_nested_metas(&field.attrs)
+                .iter()
+                .find_map(|nested| match nested {
+                    NestedMeta::Meta(Meta::NameValue(MetaNameValue {
+                        path,
+                        lit: Lit::Str(str),
+                        ..
+                    })) if path.is_ident("each") => Some(LitOrError::Lit(str.clone())),
+                    NestedMeta::Meta(Meta::NameValue(MetaNameValue { path, .. }))
+                        if path.is_ident("each") =>
+                    {
+                        Some(LitOrError::Error(syn::Error::new_spanned(
+                            nested.clone(),
+                            "expected `builder(each = \"...\")`",
+                        )))
                     }
                     _ => None,
-                },
-                _ => None,
-            })
-            .flatten();
+                });
 
         let ident = field.ident.as_ref();
         let ty = unwrap_option(&field.ty).unwrap_or(&field.ty);
+        let into = struct_setter_into || has_setter_into(&field.attrs);
         // #[builder(each = "name")]
         match ident_each_name {
-            Some(LitOrError::Lit(name)) => {
-                let ty_each = unwrap_vector(ty).unwrap();
-                let ident_each = Ident::new(name.as_str(), Span::call_site());
-                // if the name specified in "each" is the same as the field name
-                if ident.unwrap().to_string() == name {
-                    // Define only a method to add one element
-                    quote! {
-                        pub fn #ident_each(&mut self, #ident_each:#ty_each) -> &mut Self {
-                            self.#ident.push(#ident_each);
-                            self
+            Some(LitOrError::Lit(lit)) => {
+                let name = lit.value();
+                let ident_each = match syn::parse_str::<Ident>(&name) {
+                    Ok(ident_each) => ident_each,
+                    Err(_) => {
+                        errors.push(syn::Error::new_spanned(
+                            &lit,
+                            format!("`{}` is not a valid identifier", name),
+                        ));
+                        Ident::new("_invalid_each", Span::call_site())
+                    }
+                };
+                let each_setter = if let Some((key_ty, value_ty)) = unwrap_map(ty) {
+                    if into {
+                        quote! {
+                            pub fn #ident_each<__K: std::convert::Into<#key_ty>, __V: std::convert::Into<#value_ty>>(&mut self, key: __K, value: __V) -> &mut Self {
+                                self.#ident.insert(key.into(), value.into());
+                                self
+                            }
+                        }
+                    } else {
+                        quote! {
+                            pub fn #ident_each(&mut self, key: #key_ty, value: #value_ty) -> &mut Self {
+                                self.#ident.insert(key, value);
+                                self
+                            }
+                        }
+                    }
+                } else if let Some(ty_each) = unwrap_vector(ty) {
+                    if into {
+                        quote! {
+                            pub fn #ident_each<__V: std::convert::Into<#ty_each>>(&mut self, #ident_each: __V) -> &mut Self {
+                                self.#ident.push(#ident_each.into());
+                                self
+                            }
+                        }
+                    } else {
+                        quote! {
+                            pub fn #ident_each(&mut self, #ident_each: #ty_each) -> &mut Self {
+                                self.#ident.push(#ident_each);
+                                self
+                            }
                         }
                     }
                 } else {
-                    quote! {
-                        pub fn #ident(&mut self, #ident: #ty) -> &mut Self {
-                            self.#ident = #ident;
-                            self
+                    errors.push(syn::Error::new_spanned(
+                        ty,
+                        "#[builder(each = \"...\")] requires a Vec or Map field",
+                    ));
+                    TokenStream::new()
+                };
+                // if the name specified in "each" is the same as the field name
+                if *ident.unwrap() == name {
+                    // Define only a method to add one element
+                    each_setter
+                } else {
+                    let full_setter = if into {
+                        quote! {
+                            pub fn #ident<__V: std::convert::Into<#ty>>(&mut self, value: __V) -> &mut Self {
+                                self.#ident = value.into();
+                                self
+                            }
                         }
-                        pub fn #ident_each(&mut self, #ident_each: #ty_each) -> &mut Self {
-                            self.#ident.push(#ident_each);
-                            self
+                    } else {
+                        quote! {
+                            pub fn #ident(&mut self, #ident: #ty) -> &mut Self {
+                                self.#ident = #ident;
+                                self
+                            }
                         }
+                    };
+                    quote! {
+                        #full_setter
+                        #each_setter
                     }
                 }
             }
-            Some(LitOrError::Error(err)) => err.to_compile_error().into(),
+            Some(LitOrError::Error(err)) => {
+                errors.push(err);
+                TokenStream::new()
+            }
             None => {
-                if is_vector(&ty) {
+                if is_vector(ty) || is_map(ty) {
+                    if into {
+                        quote! {
+                            pub fn #ident<__V: std::convert::Into<#ty>>(&mut self, value: __V) -> &mut Self {
+                                self.#ident = value.into();
+                                self
+                            }
+                        }
+                    } else {
+                        quote! {
+                            pub fn #ident(&mut self, #ident: #ty) -> &mut Self {
+                                self.#ident = #ident;
+                                self
+                            }
+                        }
+                    }
+                } else if into {
                     quote! {
-                        pub fn #ident(&mut self, #ident: #ty) -> &mut Self {
-                            self.#ident = #ident;
+                        pub fn #ident<__V: std::convert::Into<#ty>>(&mut self, value: __V) -> &mut Self {
+                            self.#ident = std::option::Option::Some(value.into());
                             self
                         }
                     }
@@ -174,26 +343,38 @@ fn build_builder_impl(
                 }
             }
         }
-    });
+    })
+    .collect::<Vec<_>>();
 
+    // `build(&mut self)` has exclusive access to the builder, so fields are
+    // moved out rather than cloned -- this keeps an implicit `Clone` bound
+    // off of generic field types.
     let struct_fields = fields.named.iter().map(|field| {
         let ident = field.ident.as_ref();
-        if is_option(&field.ty) || is_vector(&field.ty) {
+        if is_vector(&field.ty) || is_map(&field.ty) {
+            quote! {
+                #ident: std::mem::take(&mut self.#ident)
+            }
+        } else if is_option(&field.ty) {
+            quote! {
+                #ident: self.#ident.take()
+            }
+        } else if let Some(default) = field_default_expr(field, errors) {
             quote! {
-                #ident: self.#ident.clone()
+                #ident: self.#ident.take().unwrap_or_else(|| #default)
             }
         } else {
             quote! {
-                #ident: self.#ident.clone().unwrap()
+                #ident: self.#ident.take().unwrap()
             }
         }
     });
 
     quote! {
-        impl #builder_name {
+        impl #impl_generics #builder_name #ty_generics #where_clause {
             #(#setters)*
 
-            pub fn build(&mut self) -> std::result::Result<#struct_name, std::boxed::Box<dyn std::error::Error>> {
+            pub fn build(&mut self) -> std::result::Result<#struct_name #ty_generics, #error_name> {
                 #(#checks)*
                 Ok(#struct_name {
                     #(#struct_fields),*
@@ -207,14 +388,21 @@ fn build_struct_impl(
     fields: &FieldsNamed,
     builder_name: &Ident,
     struct_name: &Ident,
+    impl_generics: &ImplGenerics,
+    ty_generics: &TypeGenerics,
+    where_clause: Option<&WhereClause>,
 ) -> TokenStream {
     let field_defaults = fields.named.iter().map(|field| {
         let ident = field.ident.as_ref();
         let ty = &field.ty;
-        if is_vector(&ty) {
+        if is_vector(ty) {
             quote! {
                 #ident: Vec::new()
             }
+        } else if is_map(ty) {
+            quote! {
+                #ident: Default::default()
+            }
         } else {
             quote! {
                 #ident: None
@@ -222,8 +410,8 @@ fn build_struct_impl(
         }
     });
     quote! {
-        impl #struct_name {
-            pub fn builder() -> #builder_name {
+        impl #impl_generics #struct_name #ty_generics #where_clause {
+            pub fn builder() -> #builder_name #ty_generics {
                 #builder_name {
                     #(#field_defaults),*
                 }
@@ -232,6 +420,62 @@ fn build_struct_impl(
     }
 }
 
+// #[builder(...)] may legally appear more than once on the same field (e.g.
+// `setter(into)` and `default` written as separate attributes); collect the
+// nested metas across all of them instead of looking only at the first.
+fn builder_nested_metas(attrs: &[syn::Attribute]) -> Vec<NestedMeta> {
+    attrs
+        .iter()
+        .filter_map(|attr| match attr.parse_meta() {
+            Ok(Meta::List(list)) if list.path.is_ident("builder") => Some(list.nested),
+            _ => None,
+        })
+        .flatten()
+        .collect()
+}
+
+// #[builder(setter(into))], readable on either the struct or a field
+fn has_setter_into(attrs: &[syn::Attribute]) -> bool {
+    builder_nested_metas(attrs)
+        .iter()
+        .any(|nested| match nested {
+            NestedMeta::Meta(Meta::List(inner)) if inner.path.is_ident("setter") => inner
+                .nested
+                .iter()
+                .any(|n| matches!(n, NestedMeta::Meta(Meta::Path(p)) if p.is_ident("into"))),
+            _ => false,
+        })
+}
+
+// #[builder(default = "expr")] or bare #[builder(default)]; returns the
+// unparsed expression source, or `None` if the field has no default.
+fn field_default_source(field: &Field) -> Option<String> {
+    builder_nested_metas(&field.attrs)
+        .iter()
+        .find_map(|nested| match nested {
+            NestedMeta::Meta(Meta::NameValue(MetaNameValue {
+                path,
+                lit: Lit::Str(str),
+                ..
+            })) if path.is_ident("default") => Some(str.value()),
+            NestedMeta::Meta(Meta::Path(path)) if path.is_ident("default") => {
+                Some("std::default::Default::default()".to_string())
+            }
+            _ => None,
+        })
+}
+
+fn field_default_expr(field: &Field, errors: &mut Vec<syn::Error>) -> Option<syn::Expr> {
+    let source = field_default_source(field)?;
+    match syn::parse_str::<syn::Expr>(&source) {
+        Ok(expr) => Some(expr),
+        Err(err) => {
+            errors.push(err);
+            Some(syn::parse_str::<syn::Expr>("()").unwrap())
+        }
+    }
+}
+
 fn get_last_path_segment(ty: &Type) -> Option<&PathSegment> {
     match ty {
         Type::Path(path) => path.path.segments.last(),
@@ -253,6 +497,34 @@ fn is_vector(ty: &Type) -> bool {
     }
 }
 
+fn is_map(ty: &Type) -> bool {
+    match get_last_path_segment(ty) {
+        Some(seg) => seg.ident == "HashMap" || seg.ident == "BTreeMap",
+        _ => false,
+    }
+}
+
+fn unwrap_map(ty: &Type) -> Option<(&Type, &Type)> {
+    if !is_map(ty) {
+        return None;
+    }
+    match get_last_path_segment(ty) {
+        Some(seg) => match seg.arguments {
+            PathArguments::AngleBracketed(ref args) => {
+                let mut types = args.args.iter().filter_map(|arg| match arg {
+                    GenericArgument::Type(ty) => Some(ty),
+                    _ => None,
+                });
+                let key = types.next()?;
+                let value = types.next()?;
+                Some((key, value))
+            }
+            _ => None,
+        },
+        None => None,
+    }
+}
+
 fn unwrap_option(ty: &Type) -> Option<&Type> {
     if !is_option(ty) {
         return None;
@@ -272,7 +544,7 @@ fn unwrap_generic_type(ty: &Type) -> Option<&Type> {
         Some(seg) => match seg.arguments {
             PathArguments::AngleBracketed(ref args) => {
                 args.args.first().and_then(|arg| match arg {
-                    &GenericArgument::Type(ref ty) => Some(ty),
+                    GenericArgument::Type(ty) => Some(ty),
                     _ => None,
                 })
             }
@@ -0,0 +1,11 @@
+use builder::Builder;
+
+#[derive(Builder)]
+pub struct Bad {
+    #[builder(each = "x")]
+    a: u32,
+    #[builder(default = "not valid rust (((")]
+    b: u32,
+}
+
+fn main() {}
@@ -0,0 +1,9 @@
+use builder::Builder;
+
+#[derive(Builder)]
+pub struct Command {
+    #[builder(each = "123bad name")]
+    args: Vec<String>,
+}
+
+fn main() {}
@@ -0,0 +1,30 @@
+use builder::Builder;
+
+#[derive(Builder)]
+pub struct Config {
+    #[builder(default = "10")]
+    timeout: u32,
+    #[builder(default)]
+    retries: u32,
+    // The `default` attributes above are unrelated to `each`; they must not
+    // be mistaken for a malformed `each` or block generating this setter.
+    #[builder(each = "flag")]
+    flags: Vec<String>,
+}
+
+fn main() {
+    let config = Config::builder()
+        .flag("verbose".to_owned())
+        .build()
+        .unwrap();
+    assert_eq!(config.timeout, 10);
+    assert_eq!(config.retries, 0);
+    assert_eq!(config.flags, vec!["verbose".to_owned()]);
+
+    let config = Config::builder()
+        .timeout(5)
+        .flag("quiet".to_owned())
+        .build()
+        .unwrap();
+    assert_eq!(config.timeout, 5);
+}
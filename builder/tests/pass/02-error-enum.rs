@@ -0,0 +1,35 @@
+use builder::Builder;
+
+#[derive(Builder)]
+pub struct Command {
+    executable: String,
+    args: Vec<String>,
+}
+
+#[derive(Builder)]
+pub struct AllOptional {
+    #[builder(default = "1")]
+    a: u32,
+}
+
+fn main() {
+    match Command::builder().build() {
+        Err(CommandBuilderError::MissingExecutable) => {}
+        _ => panic!("expected MissingExecutable"),
+    }
+    assert_eq!(
+        CommandBuilderError::MissingExecutable.to_string(),
+        "Required field 'executable' is missing"
+    );
+
+    let command = Command::builder()
+        .executable("cargo".to_owned())
+        .build()
+        .unwrap();
+    assert_eq!(command.executable, "cargo");
+
+    // A struct with no required fields must still generate a (zero-variant)
+    // error enum whose Display impl compiles.
+    let all_optional = AllOptional::builder().build().unwrap();
+    assert_eq!(all_optional.a, 1);
+}
@@ -0,0 +1,38 @@
+use builder::Builder;
+
+// `T` carries no implicit `Clone` bound, since `build()` moves fields out
+// rather than cloning them.
+#[derive(Builder)]
+pub struct Wrapper<T> {
+    value: T,
+    #[builder(default)]
+    label: String,
+}
+
+// Option<T>/Vec<T> fields move out too, so T needs no Clone bound there
+// either.
+#[derive(Builder)]
+pub struct Container<T> {
+    value: Option<T>,
+    items: Vec<T>,
+}
+
+#[derive(Debug, PartialEq)]
+struct NotClone(u32);
+
+fn main() {
+    let wrapper = Wrapper::<NotClone>::builder()
+        .value(NotClone(42))
+        .build()
+        .unwrap();
+    assert_eq!(wrapper.value, NotClone(42));
+    assert_eq!(wrapper.label, "");
+
+    let container = Container::<NotClone>::builder()
+        .value(NotClone(1))
+        .items(vec![NotClone(2), NotClone(3)])
+        .build()
+        .unwrap();
+    assert_eq!(container.value, Some(NotClone(1)));
+    assert_eq!(container.items, vec![NotClone(2), NotClone(3)]);
+}
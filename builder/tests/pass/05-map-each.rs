@@ -0,0 +1,18 @@
+use builder::Builder;
+use std::collections::HashMap;
+
+#[derive(Builder)]
+pub struct Command {
+    #[builder(each = "env")]
+    env: HashMap<String, String>,
+}
+
+fn main() {
+    let command = Command::builder()
+        .env("PATH".to_owned(), "/usr/bin".to_owned())
+        .env("HOME".to_owned(), "/root".to_owned())
+        .build()
+        .unwrap();
+    assert_eq!(command.env.get("PATH"), Some(&"/usr/bin".to_owned()));
+    assert_eq!(command.env.len(), 2);
+}
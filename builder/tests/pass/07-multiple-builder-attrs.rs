@@ -0,0 +1,16 @@
+use builder::Builder;
+
+#[derive(Builder)]
+pub struct Config {
+    #[builder(setter(into))]
+    #[builder(default = "5")]
+    count: u32,
+}
+
+fn main() {
+    let config = Config::builder().build().unwrap();
+    assert_eq!(config.count, 5);
+
+    let config = Config::builder().count(10u32).build().unwrap();
+    assert_eq!(config.count, 10);
+}